@@ -2,8 +2,9 @@ use std::{
     collections::hash_map::DefaultHasher,
     env, fs,
     hash::{Hash, Hasher},
-    path::PathBuf,
+    path::{Component, Path, PathBuf},
     str::FromStr,
+    time::SystemTime,
 };
 
 use proc_macro::TokenStream;
@@ -28,11 +29,11 @@ pub fn include_blob(args: TokenStream) -> TokenStream {
     });
     let metadata = fs::metadata(&path).unwrap();
     assert!(metadata.is_file());
-    let len = metadata.len();
+    let content = fs::read(&path).unwrap();
+    let len = content.len();
 
     let mut hasher = DefaultHasher::new();
-    path.hash(&mut hasher);
-    metadata.modified().unwrap().hash(&mut hasher);
+    content.hash(&mut hasher);
     let unique_name = format!("include_blob_{:016x}", hasher.finish());
 
     TokenStream::from_str(&format!(
@@ -48,3 +49,233 @@ pub fn include_blob(args: TokenStream) -> TokenStream {
     ))
     .unwrap()
 }
+
+/// Includes a binary file that was prepared for inclusion (with compression) by a build script.
+///
+/// Takes a string literal as its argument, denoting the file's path (relative to the directory
+/// containing the package's `Cargo.toml`). The file must have been registered via
+/// [`make_includable_compressed`](https://docs.rs/include-blob/*/include_blob/fn.make_includable_compressed.html).
+///
+/// The macro expands to an expression of type `&'static [u8]`. The blob is decompressed once, on
+/// first access, and cached for the remaining lifetime of the program.
+#[proc_macro]
+pub fn include_blob_decompressed(args: TokenStream) -> TokenStream {
+    let lit: syn::LitStr = syn::parse(args).unwrap();
+    let lit = lit.value();
+
+    let mut path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push(lit);
+
+    let path = path.canonicalize().unwrap_or_else(|_| {
+        panic!("could not find file '{}'", path.display(),);
+    });
+    let metadata = fs::metadata(&path).unwrap();
+    assert!(metadata.is_file());
+    let content = fs::read(&path).unwrap();
+
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    let unique_name = format!("include_blob_{:016x}", hasher.finish());
+    let meta_name = format!("{unique_name}_meta");
+
+    TokenStream::from_str(&format!(
+        r#"
+        {{
+            extern "C" {{
+                #[link_name = "{unique_name}"]
+                static DATA: u8;
+                #[link_name = "{meta_name}"]
+                static META: [u8; 17];
+            }}
+            static CACHE: ::std::sync::OnceLock<::std::vec::Vec<u8>> = ::std::sync::OnceLock::new();
+            CACHE
+                .get_or_init(|| unsafe {{
+                    ::include_blob::__decompress_from_ptr(&DATA as *const u8, &META)
+                }})
+                .as_slice()
+        }}
+        "#
+    ))
+    .unwrap()
+}
+
+/// Links to a directory bundle prepared by
+/// [`make_includable_dir`](https://docs.rs/include-blob/*/include_blob/fn.make_includable_dir.html)
+/// and exposes a read-only, path-indexed view of its files.
+///
+/// Takes a string literal as its argument, denoting the directory's path (relative to the
+/// directory containing the package's `Cargo.toml`).
+///
+/// The macro expands to a value with `get(&self, path: &str) -> Option<&'static [u8]>` (binary
+/// search over the normalized, `/`-separated relative paths) and `files(&self) -> impl
+/// Iterator<Item = &'static str>`.
+#[proc_macro]
+pub fn include_blob_dir(args: TokenStream) -> TokenStream {
+    let lit: syn::LitStr = syn::parse(args).unwrap();
+    let lit = lit.value();
+
+    let mut root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    root.push(lit);
+
+    let root = root.canonicalize().unwrap_or_else(|_| {
+        panic!("could not find directory '{}'", root.display());
+    });
+    assert!(fs::metadata(&root).unwrap().is_dir());
+
+    let mut files = collect_files(&root, &root);
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    let mut total_len = 0u64;
+    for (rel_path, mtime, len) in &files {
+        rel_path.hash(&mut hasher);
+        mtime.hash(&mut hasher);
+        total_len += len;
+    }
+    let unique_name = format!("include_blob_dir_{:016x}", hasher.finish());
+    let index_path = format!("{unique_name}_index.rs");
+
+    TokenStream::from_str(&format!(
+        r#"
+        {{
+            extern "C" {{
+                #[link_name = "{unique_name}"]
+                static DATA: [u8; {total_len}];
+            }}
+            static INDEX: &[(&str, usize, usize)] =
+                include!(concat!(env!("OUT_DIR"), "/{index_path}"));
+
+            struct __IncludeBlobDir;
+
+            impl __IncludeBlobDir {{
+                #[allow(dead_code)]
+                fn get(&self, path: &str) -> ::std::option::Option<&'static [u8]> {{
+                    let idx = INDEX.binary_search_by_key(&path, |entry| entry.0).ok()?;
+                    let (_, offset, len) = INDEX[idx];
+                    Some(unsafe {{ &DATA[offset..offset + len] }})
+                }}
+
+                #[allow(dead_code)]
+                fn files(&self) -> impl ::std::iter::Iterator<Item = &'static str> {{
+                    INDEX.iter().map(|entry| entry.0)
+                }}
+            }}
+
+            __IncludeBlobDir
+        }}
+        "#
+    ))
+    .unwrap()
+}
+
+/// Links to a blob prepared by
+/// [`make_includable_aligned`](https://docs.rs/include-blob/*/include_blob/fn.make_includable_aligned.html)
+/// and reinterprets it as a slice of `T`, without copying.
+///
+/// Takes a string literal (the file's path, relative to the directory containing the package's
+/// `Cargo.toml`) and a type, separated by a comma: `include_blob_as!("path", u32)`.
+///
+/// The macro expands to an expression of type `&'static [T]`. It asserts that the blob's length
+/// is a multiple of `size_of::<T>()` and that the blob is sufficiently aligned for `T` (i.e. that
+/// the file was registered with an alignment covering `align_of::<T>()`).
+#[proc_macro]
+pub fn include_blob_as(args: TokenStream) -> TokenStream {
+    let tokens: Vec<_> = args.into_iter().collect();
+    let comma = tokens
+        .iter()
+        .position(|tt| matches!(tt, proc_macro::TokenTree::Punct(p) if p.as_char() == ','))
+        .expect("expected `include_blob_as!(\"path\", T)`");
+    let lit_tokens: TokenStream = tokens[..comma].iter().cloned().collect();
+    let ty_tokens: TokenStream = tokens[comma + 1..].iter().cloned().collect();
+    assert!(!ty_tokens.is_empty(), "expected `include_blob_as!(\"path\", T)`");
+    let ty = ty_tokens.to_string();
+
+    let lit: syn::LitStr = syn::parse(lit_tokens).unwrap();
+    let lit = lit.value();
+
+    let mut path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push(lit);
+
+    let path = path.canonicalize().unwrap_or_else(|_| {
+        panic!("could not find file '{}'", path.display(),);
+    });
+    let metadata = fs::metadata(&path).unwrap();
+    assert!(metadata.is_file());
+    let content = fs::read(&path).unwrap();
+    let len = content.len();
+
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    let unique_name = format!("include_blob_{:016x}", hasher.finish());
+
+    TokenStream::from_str(&format!(
+        r#"
+        {{
+            extern "C" {{
+                #[link_name = "{unique_name}"]
+                static STATIC: [u8; {len}];
+            }}
+            type T = {ty};
+            const _: () = assert!(
+                {len} % ::std::mem::size_of::<T>() == 0,
+                "blob length is not a multiple of size_of::<T>()",
+            );
+            unsafe {{
+                let ptr = STATIC.as_ptr();
+                assert_eq!(
+                    ptr.align_offset(::std::mem::align_of::<T>()),
+                    0,
+                    "blob is not aligned for T; make_includable_aligned must be called with an \
+                     alignment covering align_of::<T>()",
+                );
+                ::std::slice::from_raw_parts(ptr as *const T, {len} / ::std::mem::size_of::<T>())
+            }}
+        }}
+        "#
+    ))
+    .unwrap()
+}
+
+fn collect_files(root: &Path, dir: &Path) -> Vec<(String, SystemTime, u64)> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        let metadata = entry.metadata().unwrap();
+        let path = entry.path();
+        if metadata.is_dir() {
+            files.extend(collect_files(root, &path));
+        } else if metadata.is_file() {
+            files.push((
+                normalize_rel_path(root, &path),
+                metadata.modified().unwrap(),
+                metadata.len(),
+            ));
+        }
+    }
+    files
+}
+
+/// Normalizes `path` (relative to `root`) the way the `tar` crate normalizes entry paths: forward
+/// slashes, no `.` components, and `..` components are rejected outright.
+fn normalize_rel_path(root: &Path, path: &Path) -> String {
+    let rel = path
+        .strip_prefix(root)
+        .expect("file is not inside the bundled directory");
+    let mut parts = Vec::new();
+    for component in rel.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().expect("non UTF-8 path").to_owned()),
+            Component::CurDir => {}
+            Component::ParentDir => panic!(
+                "'..' components are not allowed in bundled directories (found in '{}')",
+                path.display()
+            ),
+            other => panic!(
+                "unexpected path component '{other:?}' in '{}'",
+                path.display()
+            ),
+        }
+    }
+    parts.join("/")
+}
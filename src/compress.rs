@@ -0,0 +1,131 @@
+//! Compression codecs used by [`make_includable_compressed`](crate::make_includable_compressed)
+//! and [`include_blob_decompressed!`](crate::include_blob_decompressed).
+
+use std::io::{self, Read};
+
+/// A compression codec that can be applied to a blob before it is embedded into the binary.
+///
+/// Compressing a blob trades a bit of startup CPU time (the blob is decompressed once, on first
+/// access) for a smaller binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Codec {
+    /// [Zstandard](https://facebook.github.io/zstd/): fast to decompress, a good default.
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// LZMA (preset 9 with a 64 MiB dictionary window, for good ratios on big assets), written in
+    /// the legacy `.lzma` format: the LZMA1 filter, not an LZMA2 `.xz` container.
+    #[cfg(feature = "xz")]
+    Xz,
+}
+
+impl Codec {
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => 0,
+            #[cfg(feature = "xz")]
+            Codec::Xz => 1,
+        }
+    }
+
+    pub(crate) fn from_tag(tag: u8) -> Self {
+        match tag {
+            #[cfg(feature = "zstd")]
+            0 => Codec::Zstd,
+            #[cfg(feature = "xz")]
+            1 => Codec::Xz,
+            _ => panic!(
+                "blob was compressed with an unknown codec (tag {tag}); \
+                 is the matching codec feature of `include-blob` enabled?"
+            ),
+        }
+    }
+
+    pub(crate) fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => zstd::stream::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL),
+            #[cfg(feature = "xz")]
+            Codec::Xz => {
+                let mut opts = xz2::stream::LzmaOptions::new_preset(9)?;
+                opts.dict_size(64 * 1024 * 1024);
+                let stream = xz2::stream::Stream::new_lzma_encoder(&opts)?;
+                let mut encoder = xz2::write::XzEncoder::new_stream(Vec::new(), stream);
+                io::Write::write_all(&mut encoder, data)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    fn decompress_reader<'a>(self, reader: impl Read + 'a) -> Box<dyn Read + 'a> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Box::new(zstd::stream::Decoder::new(reader).expect("corrupted zstd blob")),
+            #[cfg(feature = "xz")]
+            Codec::Xz => {
+                let stream = xz2::stream::Stream::new_lzma_decoder(u64::MAX).expect("invalid xz decoder");
+                Box::new(xz2::read::XzDecoder::new_stream(reader, stream))
+            }
+        }
+    }
+}
+
+/// Not public API; lets integration tests exercise the real compression path instead of
+/// duplicating (and risking drift from) `Codec`'s actual container format.
+#[doc(hidden)]
+pub fn __compress(codec: Codec, data: &[u8]) -> io::Result<Vec<u8>> {
+    codec.compress(data)
+}
+
+/// A [`Read`] implementation that walks a bounded region of memory starting at a raw pointer,
+/// reporting EOF once `remaining` bytes have been read.
+///
+/// The bound matters, not just as a safety measure: without it, the reader never reports EOF, so
+/// a multi-frame-capable decoder (which both `zstd` and `xz` are) will, after decoding the one
+/// real frame, try to parse whatever bytes happen to follow the symbol in memory as a second
+/// frame and fail on its garbage magic number.
+struct RawPtrReader {
+    ptr: *const u8,
+    remaining: usize,
+}
+
+impl Read for RawPtrReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.remaining);
+        // SAFETY: the caller of `decompress_from_ptr` guarantees that `ptr` points at the start
+        // of `remaining` bytes of valid, readable memory holding the compressed frame.
+        unsafe {
+            std::ptr::copy_nonoverlapping(self.ptr, buf.as_mut_ptr(), len);
+            self.ptr = self.ptr.add(len);
+        }
+        self.remaining -= len;
+        Ok(len)
+    }
+}
+
+/// Decompresses a blob linked into the binary at `ptr`, using the codec, decompressed length, and
+/// compressed length recorded in `meta` (a tag byte followed by two 8-byte little-endian lengths).
+///
+/// Not public API; called by the code generated from [`include_blob_decompressed!`].
+///
+/// # Safety
+///
+/// `ptr` must point at the start of a complete compressed frame produced by [`Codec::compress`],
+/// followed by at least `meta`'s compressed length of valid, readable memory.
+#[doc(hidden)]
+pub unsafe fn decompress_from_ptr(ptr: *const u8, meta: &[u8; 17]) -> Vec<u8> {
+    let codec = Codec::from_tag(meta[0]);
+    let decompressed_len = u64::from_le_bytes(meta[1..9].try_into().unwrap()) as usize;
+    let compressed_len = u64::from_le_bytes(meta[9..17].try_into().unwrap()) as usize;
+
+    let mut out = Vec::with_capacity(decompressed_len);
+    codec
+        .decompress_reader(RawPtrReader {
+            ptr,
+            remaining: compressed_len,
+        })
+        .read_to_end(&mut out)
+        .expect("corrupted blob");
+    out
+}
@@ -0,0 +1,153 @@
+//! Bundling of entire directories into a single linked blob, for use with
+//! [`include_blob_dir!`](crate::include_blob_dir).
+
+use crate::{lib_prefix_and_suffix, write_archive, Result, TargetInfo};
+use object::{
+    write::{Object, StandardSection, Symbol, SymbolSection},
+    SymbolFlags, SymbolKind, SymbolScope,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    env,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::Write as _,
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Call this from your build script to bundle every file under `path` into a single linked blob,
+/// accessible via [`include_blob_dir!`](crate::include_blob_dir).
+///
+/// Unlike [`make_includable`](crate::make_includable), this does not require every file to be
+/// named individually at the call site: the bundle is indexed by its (normalized) path relative
+/// to `path`.
+pub fn make_includable_dir<A: AsRef<Path>>(path: A) {
+    make_includable_dir_impl(path.as_ref()).unwrap();
+}
+
+fn make_includable_dir_impl(path: &Path) -> Result<()> {
+    let path = path.canonicalize().unwrap_or_else(|_| {
+        panic!(
+            "could not find directory '{}' (working directory is '{}')",
+            path.display(),
+            std::env::current_dir().unwrap().display(),
+        );
+    });
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    let mut files = collect_files(&path, &path)?;
+    files.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    for file in &files {
+        file.rel_path.hash(&mut hasher);
+        file.mtime.hash(&mut hasher);
+    }
+    let unique_name = format!("include_blob_dir_{:016x}", hasher.finish());
+
+    let mut content = Vec::new();
+    let mut index = Vec::with_capacity(files.len());
+    for file in &files {
+        let bytes = fs::read(&file.abs_path)?;
+        let offset = content.len();
+        content.extend_from_slice(&bytes);
+        index.push((file.rel_path.clone(), offset, bytes.len()));
+    }
+
+    let info = TargetInfo::from_build_script_vars();
+    let (pre, post) = lib_prefix_and_suffix(&info);
+    let out_dir = env::var("OUT_DIR")?;
+    let out_file_path = format!("{out_dir}/{pre}{unique_name}{post}");
+    let mut out_file = File::create(&out_file_path)?;
+
+    let mut obj_buf = Vec::new();
+    let mut object = Object::new(info.binfmt, info.arch, info.endian);
+    let section = object.add_subsection(StandardSection::ReadOnlyData, unique_name.as_bytes());
+    let sym = object.add_symbol(Symbol {
+        name: unique_name.as_bytes().to_vec(),
+        value: 0,
+        size: content.len() as _,
+        kind: SymbolKind::Data,
+        scope: SymbolScope::Linkage,
+        weak: false,
+        section: SymbolSection::Section(section),
+        flags: SymbolFlags::None,
+    });
+    object.add_symbol_data(sym, section, &content, 1);
+    object.write_stream(&mut obj_buf)?;
+
+    let object_file_name = format!("{unique_name}.o").into_bytes();
+    write_archive(&info, &mut out_file, &object_file_name, &obj_buf)?;
+
+    println!("cargo:rustc-link-lib=static={unique_name}");
+    println!("cargo:rustc-link-search=native={out_dir}");
+
+    let index_path = format!("{out_dir}/{unique_name}_index.rs");
+    let mut index_file = File::create(&index_path)?;
+    write_index(&mut index_file, &index)?;
+
+    Ok(())
+}
+
+struct FileEntry {
+    rel_path: String,
+    abs_path: PathBuf,
+    mtime: SystemTime,
+}
+
+fn collect_files(root: &Path, dir: &Path) -> Result<Vec<FileEntry>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let abs_path = entry.path();
+        if metadata.is_dir() {
+            files.extend(collect_files(root, &abs_path)?);
+        } else if metadata.is_file() {
+            files.push(FileEntry {
+                rel_path: normalize_rel_path(root, &abs_path),
+                mtime: metadata.modified()?,
+                abs_path,
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// Normalizes `path` (relative to `root`) the way the `tar` crate normalizes entry paths: forward
+/// slashes, no `.` components, and `..` components are rejected outright.
+fn normalize_rel_path(root: &Path, path: &Path) -> String {
+    let rel = path
+        .strip_prefix(root)
+        .expect("file is not inside the bundled directory");
+    let mut parts = Vec::new();
+    for component in rel.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().expect("non UTF-8 path").to_owned()),
+            Component::CurDir => {}
+            Component::ParentDir => panic!(
+                "'..' components are not allowed in bundled directories (found in '{}')",
+                path.display()
+            ),
+            other => panic!(
+                "unexpected path component '{other:?}' in '{}'",
+                path.display()
+            ),
+        }
+    }
+    parts.join("/")
+}
+
+/// Writes just the array *value* (no `static ... = ` item wrapper, no trailing `;`): the macro
+/// side `include!`s this as the initializer of its own `static INDEX` item, and `include!`-ing a
+/// full item at statement position inside a block is not valid Rust.
+fn write_index(out: &mut File, index: &[(String, usize, usize)]) -> Result<()> {
+    writeln!(out, "&[")?;
+    for (rel_path, offset, len) in index {
+        writeln!(out, "    ({rel_path:?}, {offset}, {len}),")?;
+    }
+    writeln!(out, "]")?;
+    Ok(())
+}
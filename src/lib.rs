@@ -18,24 +18,36 @@
 //! let bytes: &[u8] = include_blob::include_blob!("test-project/blobs/file.txt");
 //! ```
 
-use ar_archive_writer::{
-    write_archive_to_stream, ArchiveKind, NewArchiveMember, DEFAULT_OBJECT_READER,
-};
+use ar_archive_writer::{write_archive_to_stream, NewArchiveMember, DEFAULT_OBJECT_READER};
 use object::{
     write::{Object, StandardSection, Symbol, SymbolSection},
-    Architecture, BinaryFormat, Endianness, SymbolFlags, SymbolKind, SymbolScope,
+    SymbolFlags, SymbolKind, SymbolScope,
 };
+
+pub use ar_archive_writer::ArchiveKind;
+pub use object::{Architecture, BinaryFormat, Endianness};
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, HashMap},
     env, error,
     fs::{self, File},
     hash::{Hash, Hasher},
     io::{Seek, Write},
     path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
 };
 
 pub use include_blob_macros::*;
 
+mod compress;
+mod dir;
+
+pub use compress::Codec;
+pub use dir::make_includable_dir;
+#[doc(hidden)]
+pub use compress::decompress_from_ptr as __decompress_from_ptr;
+#[doc(hidden)]
+pub use compress::__compress;
+
 type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 /// Call this from your build script to make `path` includable via [`include_blob!`].
@@ -45,10 +57,42 @@ type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 /// `path` is relative to the directory the build script runs in (which is the package's "source
 /// directory" according to Cargo's docs, so probably the directory containing `Cargo.toml`).
 pub fn make_includable<A: AsRef<Path>>(path: A) {
-    make_includable_impl(path.as_ref()).unwrap();
+    make_includable_impl(path.as_ref(), None, 1, None).unwrap();
 }
 
-fn make_includable_impl(path: &Path) -> Result<()> {
+/// Like [`make_includable`], but compresses the content with `codec` before embedding it.
+///
+/// Use [`include_blob_decompressed!`] (instead of [`include_blob!`]) to access blobs made
+/// includable this way; it decompresses the blob once, on first access.
+pub fn make_includable_compressed<A: AsRef<Path>>(path: A, codec: Codec) {
+    make_includable_impl(path.as_ref(), Some(codec), 1, None).unwrap();
+}
+
+/// Like [`make_includable`], but places the content at a section offset aligned to `align` bytes.
+///
+/// Use [`include_blob_as!`] (instead of [`include_blob!`]) to reinterpret blobs made includable
+/// this way as `&'static [T]` without copying, e.g. to embed a lookup table or SIMD-friendly data.
+/// `align` must cover the alignment of the type you intend to view the blob as.
+pub fn make_includable_aligned<A: AsRef<Path>>(path: A, align: u64) {
+    make_includable_impl(path.as_ref(), None, align, None).unwrap();
+}
+
+/// Like [`make_includable`], but uses `target` instead of guessing the target from
+/// `CARGO_CFG_*` build script environment variables.
+///
+/// Use this when cross-compiling to a target [`TargetInfo::from_build_script_vars`] doesn't know
+/// about (via [`TargetInfo::new`]), or to override part of its guess (e.g. via
+/// [`TargetInfo::binary_format`]).
+pub fn make_includable_with_target<A: AsRef<Path>>(path: A, target: TargetInfo) {
+    make_includable_impl(path.as_ref(), None, 1, Some(target)).unwrap();
+}
+
+fn make_includable_impl(
+    path: &Path,
+    codec: Option<Codec>,
+    align: u64,
+    target: Option<TargetInfo>,
+) -> Result<()> {
     let path = path.canonicalize().unwrap_or_else(|_| {
         panic!(
             "could not find file '{}' (working directory is '{}')",
@@ -62,11 +106,11 @@ fn make_includable_impl(path: &Path) -> Result<()> {
     if metadata.is_dir() {
         for entry in fs::read_dir(&path)? {
             let entry = entry?;
-            make_includable_impl(&entry.path())?;
+            make_includable_impl(&entry.path(), codec, align, target.clone())?;
         }
         Ok(())
     } else if metadata.is_file() {
-        process_file(path, metadata)
+        process_file(path, codec, align, target)
     } else {
         panic!(
             "cannot handle file type '{:?}' of '{}'",
@@ -76,20 +120,65 @@ fn make_includable_impl(path: &Path) -> Result<()> {
     }
 }
 
-fn process_file(path: PathBuf, metadata: fs::Metadata) -> Result<()> {
-    let mut hasher = DefaultHasher::new();
-    path.hash(&mut hasher);
-    metadata.modified()?.hash(&mut hasher);
-    let unique_name = format!("include_blob_{:016x}", hasher.finish());
+fn process_file(
+    path: PathBuf,
+    codec: Option<Codec>,
+    align: u64,
+    target: Option<TargetInfo>,
+) -> Result<()> {
+    let out_dir = env::var("OUT_DIR")?;
 
-    let content = fs::read(&path)?;
+    // The symbol identity is the content hash, not the path: byte-identical files always collapse
+    // to a single linked symbol, no matter what their paths or mtimes are. There's deliberately no
+    // separate path+mtime based fast path here (there used to be one): it skipped re-reading a
+    // previously-seen path, but that bypassed the `emitted_symbols` mode/alignment check below,
+    // letting a path registered twice with incompatible embedding modes silently reuse the first
+    // one's (wrong) symbol instead of being rejected.
+    let raw_content = fs::read(&path)?;
+    let mut content_hasher = DefaultHasher::new();
+    raw_content.hash(&mut content_hasher);
+    let unique_name = format!("include_blob_{:016x}", content_hasher.finish());
 
-    let (pre, post) = lib_prefix_and_suffix();
-    let out_dir = env::var("OUT_DIR")?;
+    println!("cargo:rustc-link-lib=static={unique_name}");
+    println!("cargo:rustc-link-search=native={out_dir}");
+
+    let mode = match codec {
+        Some(codec) => EmbedMode::Compressed(codec),
+        None => EmbedMode::Plain,
+    };
+
+    {
+        let mut emitted = emitted_symbols().lock().unwrap();
+        if let Some(&(emitted_align, emitted_mode)) = emitted.get(&unique_name) {
+            assert!(
+                emitted_mode == mode,
+                "'{}' has the same content as a file already embedded in {emitted_mode:?} mode, \
+                 but this one requests {mode:?} mode; embed identical content the same way \
+                 everywhere",
+                path.display(),
+            );
+            assert!(
+                emitted_align % align == 0,
+                "'{}' was already embedded with alignment {emitted_align}, which does not cover \
+                 the alignment {align} requested here; embed it with the larger alignment instead",
+                path.display(),
+            );
+            // Identical content has already been linked under this symbol name, by this file or
+            // an earlier one with the same content; nothing left to embed.
+            return Ok(());
+        }
+        emitted.insert(unique_name.clone(), (align, mode));
+    }
+
+    let content = match codec {
+        Some(codec) => codec.compress(&raw_content)?,
+        None => raw_content.clone(),
+    };
+
+    let info = target.unwrap_or_else(TargetInfo::from_build_script_vars);
+    let (pre, post) = lib_prefix_and_suffix(&info);
     let out_file_path = format!("{out_dir}/{pre}{unique_name}{post}");
     let mut out_file = File::create(&out_file_path)?;
-
-    let info = TargetInfo::from_build_script_vars();
     let mut obj_buf = Vec::new();
     let mut object = Object::new(info.binfmt, info.arch, info.endian);
     let section = object.add_subsection(StandardSection::ReadOnlyData, unique_name.as_bytes());
@@ -104,17 +193,62 @@ fn process_file(path: PathBuf, metadata: fs::Metadata) -> Result<()> {
         section: SymbolSection::Section(section),
         flags: SymbolFlags::None,
     });
-    object.add_symbol_data(sym, section, &content, 1);
+    object.add_symbol_data(sym, section, &content, align);
+
+    if let Some(codec) = codec {
+        // Record the codec, the decompressed length, and the compressed length, so
+        // `include_blob_decompressed!` knows exactly how many bytes to feed the decoder: without
+        // that bound, the decoder never sees EOF and tries to parse whatever follows the symbol
+        // in memory as a second frame.
+        let mut meta = [0u8; 17];
+        meta[0] = codec.tag();
+        meta[1..9].copy_from_slice(&(raw_content.len() as u64).to_le_bytes());
+        meta[9..17].copy_from_slice(&(content.len() as u64).to_le_bytes());
+
+        let meta_name = format!("{unique_name}_meta");
+        let meta_section =
+            object.add_subsection(StandardSection::ReadOnlyData, meta_name.as_bytes());
+        let meta_sym = object.add_symbol(Symbol {
+            name: meta_name.into_bytes(),
+            value: 0,
+            size: meta.len() as _,
+            kind: SymbolKind::Data,
+            scope: SymbolScope::Linkage,
+            weak: false,
+            section: SymbolSection::Section(meta_section),
+            flags: SymbolFlags::None,
+        });
+        object.add_symbol_data(meta_sym, meta_section, &meta, 1);
+    }
+
     object.write_stream(&mut obj_buf)?;
 
     let object_file_name = format!("{unique_name}.o").into_bytes();
     write_archive(&info, &mut out_file, &object_file_name, &obj_buf)?;
 
-    println!("cargo:rustc-link-lib=static={unique_name}");
-    println!("cargo:rustc-link-search=native={out_dir}");
     Ok(())
 }
 
+/// How a symbol's content was embedded: plain, or compressed with a particular [`Codec`].
+///
+/// Two files with identical raw content but different embedding modes must not collapse onto the
+/// same symbol: [`include_blob!`] and [`include_blob_decompressed!`] read that symbol completely
+/// differently, so whichever call site linked second would silently get the wrong interpretation
+/// of the data (or an undefined `_meta` symbol) instead of an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EmbedMode {
+    Plain,
+    Compressed(Codec),
+}
+
+/// Symbol names already emitted into an object file in this build script run, mapped to the
+/// alignment and [`EmbedMode`] they were embedded with, so that byte-identical files (reached via
+/// different paths, or embedded in incompatible ways) are only embedded once, or rejected.
+fn emitted_symbols() -> &'static Mutex<HashMap<String, (u64, EmbedMode)>> {
+    static EMITTED: OnceLock<Mutex<HashMap<String, (u64, EmbedMode)>>> = OnceLock::new();
+    EMITTED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
 fn write_archive(
     target_info: &TargetInfo,
     out_file: &mut (impl Write + Seek),
@@ -135,7 +269,16 @@ fn write_archive(
     Ok(())
 }
 
-struct TargetInfo {
+/// The subset of target properties needed to write an object file and archive for it.
+///
+/// Constructed from the build script's `CARGO_CFG_*` environment variables via
+/// [`TargetInfo::from_build_script_vars`]. If the guesswork that function does for a particular
+/// property is wrong for your (possibly custom or cross-compilation) target, override it with
+/// [`binary_format`](TargetInfo::binary_format), [`architecture`](TargetInfo::architecture),
+/// [`endianness`](TargetInfo::endianness), or [`archive_kind`](TargetInfo::archive_kind), and pass
+/// the result to [`make_includable_with_target`].
+#[derive(Clone)]
+pub struct TargetInfo {
     binfmt: BinaryFormat,
     arch: Architecture,
     endian: Endianness,
@@ -143,14 +286,34 @@ struct TargetInfo {
 }
 
 impl TargetInfo {
-    fn from_build_script_vars() -> Self {
-        let (binfmt, archive_kind) = match &*env::var("CARGO_CFG_TARGET_OS").unwrap() {
-            "macos" | "ios" => (BinaryFormat::MachO, ArchiveKind::Darwin64),
+    /// Guesses the target's properties from the build script's `CARGO_CFG_*` environment
+    /// variables.
+    ///
+    /// Panics if the target's operating system or architecture isn't one this function knows
+    /// about; build the [`TargetInfo`] explicitly with [`TargetInfo::new`] for such targets.
+    ///
+    /// Note that WebAssembly targets can't be supported by this at all, no matter the override:
+    /// the `object` crate this function (and [`make_includable`]) builds on has no support for
+    /// *writing* Wasm object files (only for reading them), so there is no [`BinaryFormat`] to
+    /// hand [`binary_format`](TargetInfo::binary_format) that would actually work.
+    pub fn from_build_script_vars() -> Self {
+        let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+        let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+
+        let (binfmt, archive_kind) = match &*target_os {
+            "macos" | "ios" | "tvos" | "watchos" | "visionos" => {
+                (BinaryFormat::MachO, ArchiveKind::Darwin64)
+            }
             "windows" => (BinaryFormat::Coff, ArchiveKind::Gnu),
-            "linux" | "android" => (BinaryFormat::Elf, ArchiveKind::Gnu),
-            unk => panic!("unhandled operating system '{unk}'"),
+            "linux" | "android" | "freebsd" | "netbsd" | "openbsd" | "dragonfly" | "illumos"
+            | "solaris" | "haiku" | "hurd" => (BinaryFormat::Elf, ArchiveKind::Gnu),
+            unk => panic!(
+                "unhandled operating system '{unk}'; build a `TargetInfo` with \
+                 `TargetInfo::new(..)` instead (note that \
+                 WebAssembly targets aren't supported at all, see this function's docs)"
+            ),
         };
-        let arch = match &*env::var("CARGO_CFG_TARGET_ARCH").unwrap() {
+        let arch = match &*target_arch {
             // NB: this is guesswork, because apparently the Rust team can't be bothered to document
             // the *full* list anywhere (they differ from what the target triples use, which *are*
             // fully documented)
@@ -164,7 +327,13 @@ impl TargetInfo {
             "mips64" => Architecture::Mips64,
             "powerpc" => Architecture::PowerPc,
             "powerpc64" => Architecture::PowerPc64,
-            unk => panic!("unhandled architecture '{unk}'"),
+            "s390x" => Architecture::S390x,
+            "loongarch64" => Architecture::LoongArch64,
+            unk => panic!(
+                "unhandled architecture '{unk}'; build a `TargetInfo` with \
+                 `TargetInfo::new(..)` instead (note that \
+                 WebAssembly targets aren't supported at all, see this function's docs)"
+            ),
         };
         let endian = match &*env::var("CARGO_CFG_TARGET_ENDIAN").unwrap() {
             "little" => Endianness::Little,
@@ -179,14 +348,56 @@ impl TargetInfo {
             archive_kind,
         }
     }
+
+    /// Builds a [`TargetInfo`] from explicitly given properties, without consulting any
+    /// `CARGO_CFG_*` environment variables.
+    pub fn new(
+        binfmt: BinaryFormat,
+        arch: Architecture,
+        endian: Endianness,
+        archive_kind: ArchiveKind,
+    ) -> Self {
+        Self {
+            binfmt,
+            arch,
+            endian,
+            archive_kind,
+        }
+    }
+
+    /// Overrides the object file format to emit.
+    pub fn binary_format(mut self, binfmt: BinaryFormat) -> Self {
+        self.binfmt = binfmt;
+        self
+    }
+
+    /// Overrides the target architecture recorded in the object file.
+    pub fn architecture(mut self, arch: Architecture) -> Self {
+        self.arch = arch;
+        self
+    }
+
+    /// Overrides the target byte order recorded in the object file.
+    pub fn endianness(mut self, endian: Endianness) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// Overrides the archive format used to wrap the object file.
+    pub fn archive_kind(mut self, archive_kind: ArchiveKind) -> Self {
+        self.archive_kind = archive_kind;
+        self
+    }
 }
 
-fn lib_prefix_and_suffix() -> (&'static str, &'static str) {
-    if env::var_os("CARGO_CFG_UNIX").is_some() {
-        ("lib", ".a")
-    } else if env::var_os("CARGO_CFG_WINDOWS").is_some() {
-        ("", ".lib")
-    } else {
-        unimplemented!("target platform not supported");
+/// Returns the file name prefix and suffix to use for the static library holding `target`'s
+/// object file, based on the binary format being emitted rather than the target's `CARGO_CFG_*`
+/// family cfgs (`CARGO_CFG_UNIX`/`CARGO_CFG_WINDOWS`): a target passed to
+/// [`make_includable_with_target`] may not set either of those, but still needs an archive name
+/// `rustc-link-lib` can find.
+fn lib_prefix_and_suffix(target: &TargetInfo) -> (&'static str, &'static str) {
+    match target.binfmt {
+        BinaryFormat::Coff => ("", ".lib"),
+        _ => ("lib", ".a"),
     }
 }
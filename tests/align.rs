@@ -0,0 +1,19 @@
+mod support;
+
+/// Regression test for `make_includable_aligned`'s alignment-consistency check: identical content
+/// embedded once under a smaller alignment and again under an incompatible larger one must be
+/// rejected, not silently linked under whichever alignment happened to be recorded first.
+#[test]
+#[should_panic(expected = "does not cover")]
+fn incompatible_alignment_request_is_rejected() {
+    let _guard = support::lock();
+    let (src_dir, out_dir) = support::fixture_dirs("align");
+
+    std::fs::write(src_dir.join("a.txt"), b"same bytes, different alignments").unwrap();
+    std::fs::write(src_dir.join("b.txt"), b"same bytes, different alignments").unwrap();
+
+    include_blob::make_includable_aligned(src_dir.join("a.txt"), 4);
+    include_blob::make_includable_aligned(src_dir.join("b.txt"), 16);
+
+    support::cleanup(&src_dir, &out_dir);
+}
@@ -0,0 +1,48 @@
+mod support;
+
+/// Two byte-identical files should be embedded once, not once per file.
+#[test]
+fn duplicate_content_is_embedded_once() {
+    let _guard = support::lock();
+    let (src_dir, out_dir) = support::fixture_dirs("dedup");
+
+    std::fs::write(src_dir.join("a.txt"), b"the same bytes twice over").unwrap();
+    std::fs::write(src_dir.join("b.txt"), b"the same bytes twice over").unwrap();
+
+    include_blob::make_includable(&src_dir);
+
+    let archives: Vec<_> = std::fs::read_dir(&out_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| {
+            let name = name.to_string_lossy();
+            name.ends_with(".a") || name.ends_with(".lib")
+        })
+        .collect();
+    assert_eq!(
+        archives.len(),
+        1,
+        "expected a single emitted object for identical content, got {archives:?}"
+    );
+
+    support::cleanup(&src_dir, &out_dir);
+}
+
+/// Identical content embedded once plain and once compressed must not collapse onto the same
+/// symbol: the two macros that read it back (`include_blob!` and `include_blob_decompressed!`)
+/// interpret the linked bytes completely differently.
+#[cfg(feature = "zstd")]
+#[test]
+#[should_panic(expected = "embed identical content the same way everywhere")]
+fn mismatched_embed_mode_is_rejected() {
+    let _guard = support::lock();
+    let (src_dir, out_dir) = support::fixture_dirs("dedup-mode");
+
+    std::fs::write(src_dir.join("a.txt"), b"same bytes, different embedding modes").unwrap();
+    std::fs::write(src_dir.join("b.txt"), b"same bytes, different embedding modes").unwrap();
+
+    include_blob::make_includable(&src_dir.join("a.txt"));
+    include_blob::make_includable_compressed(&src_dir.join("b.txt"), include_blob::Codec::Zstd);
+
+    support::cleanup(&src_dir, &out_dir);
+}
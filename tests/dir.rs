@@ -0,0 +1,35 @@
+mod support;
+
+/// Regression test for `make_includable_dir`'s generated index file: `include_blob_dir!` does
+/// `static INDEX: &[(&str, usize, usize)] = include!(...);`, so the generated file must contain
+/// just the array *value*, not a full `static ... = ...;` item (`include!`-ing an item at
+/// statement position inside a block does not compile).
+#[test]
+fn generated_index_is_an_array_literal_not_an_item() {
+    let _guard = support::lock();
+    let (src_dir, out_dir) = support::fixture_dirs("dir");
+
+    std::fs::create_dir_all(src_dir.join("sub")).unwrap();
+    std::fs::write(src_dir.join("a.txt"), b"hello").unwrap();
+    std::fs::write(src_dir.join("sub/b.txt"), b"world").unwrap();
+
+    include_blob::make_includable_dir(&src_dir);
+
+    let index_path = std::fs::read_dir(&out_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().ends_with("_index.rs"))
+        .expect("no index file was generated");
+    let contents = std::fs::read_to_string(&index_path).unwrap();
+
+    assert!(
+        contents.trim_start().starts_with('&'),
+        "index file must contain only the array value, got: {contents}"
+    );
+    assert!(
+        !contents.contains("static"),
+        "index file must not declare its own item, got: {contents}"
+    );
+
+    support::cleanup(&src_dir, &out_dir);
+}
@@ -0,0 +1,64 @@
+//! Fixture helpers shared by the build-script-side integration tests.
+//!
+//! These tests mutate process-global state: `env::set_var` for the `CARGO_CFG_*`/`OUT_DIR`
+//! variables `include_blob` reads, and `include_blob`'s own process-local `emitted_symbols`
+//! cache. Tests in the same file run as threads in the same process by
+//! default, so without serialization one test's env mutation can clobber another mid-build;
+//! `lock()` guards against that. Each `tests/*.rs` file is compiled into its own binary, so this
+//! lock only needs to cover tests within a single file, not across files.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Acquires the lock serializing access to process-global env/build state, recovering from a
+/// poisoned lock left behind by an earlier test's expected (`#[should_panic]`) panic instead of
+/// panicking on it too.
+pub fn lock() -> MutexGuard<'static, ()> {
+    ENV_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Creates a fresh, empty pair of temporary directories (for source files and `OUT_DIR`) scoped to
+/// `prefix`, and points the `CARGO_CFG_*` build script environment variables at the host target.
+///
+/// Call this (and keep the returned [`lock`] guard alive) for the duration of any test that calls
+/// into `include_blob`'s build-script-side functions.
+pub fn fixture_dirs(prefix: &str) -> (PathBuf, PathBuf) {
+    let run_id = format!(
+        "{:?}",
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+    );
+    let src_dir = env::temp_dir().join(format!("include-blob-{prefix}-src-{run_id}"));
+    let out_dir = env::temp_dir().join(format!("include-blob-{prefix}-out-{run_id}"));
+    fs::create_dir_all(&src_dir).unwrap();
+    fs::create_dir_all(&out_dir).unwrap();
+
+    env::set_var("OUT_DIR", &out_dir);
+    env::set_var("CARGO_CFG_TARGET_OS", env::consts::OS);
+    env::set_var("CARGO_CFG_TARGET_ARCH", env::consts::ARCH);
+    env::set_var(
+        "CARGO_CFG_TARGET_ENDIAN",
+        if cfg!(target_endian = "big") { "big" } else { "little" },
+    );
+    if cfg!(unix) {
+        env::set_var("CARGO_CFG_UNIX", "1");
+        env::remove_var("CARGO_CFG_WINDOWS");
+    } else {
+        env::set_var("CARGO_CFG_WINDOWS", "1");
+        env::remove_var("CARGO_CFG_UNIX");
+    }
+
+    (src_dir, out_dir)
+}
+
+/// Removes the directories created by [`fixture_dirs`].
+pub fn cleanup(src_dir: &Path, out_dir: &Path) {
+    fs::remove_dir_all(src_dir).ok();
+    fs::remove_dir_all(out_dir).ok();
+}
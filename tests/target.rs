@@ -0,0 +1,17 @@
+mod support;
+
+/// Regression test: unrecognized targets, including WebAssembly (which the underlying `object`
+/// crate cannot write object files for, no matter what `TargetInfo` override is supplied), must
+/// fail with `TargetInfo::from_build_script_vars`'s actionable panic message instead of either
+/// silently mis-mapping to a format the writer doesn't support, or crashing deep inside `object`.
+#[test]
+#[should_panic(expected = "WebAssembly targets aren't supported at all")]
+fn wasm_target_is_rejected_with_an_actionable_message() {
+    let _guard = support::lock();
+
+    std::env::set_var("CARGO_CFG_TARGET_ARCH", "wasm32");
+    std::env::set_var("CARGO_CFG_TARGET_OS", "unknown");
+    std::env::set_var("CARGO_CFG_TARGET_ENDIAN", "little");
+
+    include_blob::TargetInfo::from_build_script_vars();
+}